@@ -12,17 +12,29 @@
 //! ```
 //!
 //! On Linux and Mac they are implemented with [`libc::isatty`]. On Windows they
-//! are implemented with [`kernel32::GetConsoleMode`]. On Redox they are
-//! implemented with [`termion::is_tty`].
+//! are implemented with [`GetConsoleMode`]. On Redox they are implemented
+//! with [`termion::is_tty`].
 //!
 //! [`libc::isatty`]: http://man7.org/linux/man-pages/man3/isatty.3.html
-//! [`kernel32::GetConsoleMode`]: https://msdn.microsoft.com/en-us/library/windows/desktop/ms683167.aspx
+//! [`GetConsoleMode`]: https://msdn.microsoft.com/en-us/library/windows/desktop/ms683167.aspx
 //! [`termion::is_tty`]: https://docs.rs/termion/1.5.1/termion/fn.is_tty.html
 //!
-//! The `stdin_isatty` function is not yet implemented for Windows. If you need
-//! it, please check [dtolnay/isatty#1] and contribute an implementation!
+//! On Unix, with the default `std` feature turned off, the crate is built
+//! `#![no_std]`, calling straight through to [`libc::isatty`] without
+//! pulling in `std`; the `std` feature (on by default) additionally brings
+//! in the [`IsTerminal`] impl, which needs `std::os::unix::io::AsRawFd`. On
+//! Windows it is built on [`windows-sys`] rather than the older
+//! `kernel32-sys`/`winapi` crates.
 //!
-//! [dtolnay/isatty#1]: https://github.com/dtolnay/isatty/issues/1
+//! [`windows-sys`]: https://docs.rs/windows-sys
+//!
+//! On Windows, `stdin_isatty` also detects a cygwin/msys pty on the input
+//! handle, the same way `stdout_isatty`/`stderr_isatty` do, even when
+//! `GetConsoleMode` fails on it.
+//!
+//! For arbitrary file descriptors and handles, rather than just the
+//! process's own stdin/stdout/stderr, see the [`IsTerminal`] extension
+//! trait.
 //!
 //! ## Usage
 //!
@@ -47,12 +59,22 @@
 //! > ```
 
 #![doc(html_root_url = "https://docs.rs/isatty/0.1.6")]
+#![cfg_attr(unix, no_std)]
+// The usage example spells out a full `fn main` on purpose, to match what a
+// real `src/main.rs` looks like.
+#![allow(clippy::needless_doctest_main)]
 
 // Based on:
 //  - https://github.com/rust-lang/cargo/blob/099ad28104fe319f493dc42e0c694d468c65767d/src/cargo/lib.rs#L154-L178
 //  - https://github.com/BurntSushi/ripgrep/issues/94#issuecomment-261761687
 
-#[cfg(not(windows))]
+// The `IsTerminal` impl below needs `std::os::unix::io::AsRawFd`, so it (and
+// the `extern crate std` that backs it) only exists when the default `std`
+// feature is enabled. With that feature off, unix builds stay genuinely
+// `no_std`.
+#[cfg(all(unix, feature = "std"))]
+extern crate std;
+
 pub fn stdin_isatty() -> bool {
     isatty(stream::Stream::Stdin)
 }
@@ -65,9 +87,37 @@ pub fn stderr_isatty() -> bool {
     isatty(stream::Stream::Stderr)
 }
 
+/// Extension trait for asking whether an arbitrary stream is connected to a
+/// terminal.
+///
+/// The free functions above are limited to the process's own stdin/stdout/
+/// stderr. This trait is implemented for every type exposing a raw file
+/// descriptor (Unix, with the default `std` feature) or raw handle (Windows)
+/// -- including `std::io::Stdin`, `std::io::Stdout`, `std::io::Stderr`, and
+/// also e.g. `std::fs::File`, or on Unix `std::net::TcpStream`. On Windows,
+/// `std::net::TcpStream` exposes a raw socket rather than a raw handle, so it
+/// is not covered there.
+pub trait IsTerminal {
+    /// Returns `true` if this stream is a terminal/tty.
+    fn is_terminal(&self) -> bool;
+}
+
+#[cfg(all(unix, feature = "std"))]
+impl<T: ::std::os::unix::io::AsRawFd> IsTerminal for T {
+    fn is_terminal(&self) -> bool {
+        unix::isatty(self.as_raw_fd())
+    }
+}
+
+#[cfg(windows)]
+impl<T: ::std::os::windows::io::AsRawHandle> IsTerminal for T {
+    fn is_terminal(&self) -> bool {
+        windows::isatty(self.as_raw_handle())
+    }
+}
+
 mod stream {
     pub enum Stream {
-        #[cfg(not(windows))]
         Stdin,
         Stdout,
         Stderr,
@@ -75,74 +125,99 @@ mod stream {
 }
 
 #[cfg(unix)]
-use unix::isatty;
+fn isatty(stream: stream::Stream) -> bool {
+    unix::isatty(unix::stream_fd(stream))
+}
 #[cfg(unix)]
 mod unix {
+    extern crate libc;
+
     use stream::Stream;
 
-    pub fn isatty(stream: Stream) -> bool {
-        extern crate libc;
+    pub fn isatty(fd: libc::c_int) -> bool {
+        unsafe { libc::isatty(fd) != 0 }
+    }
 
-        let fd = match stream {
+    pub fn stream_fd(stream: Stream) -> libc::c_int {
+        match stream {
             Stream::Stdin => libc::STDIN_FILENO,
             Stream::Stdout => libc::STDOUT_FILENO,
             Stream::Stderr => libc::STDERR_FILENO,
-        };
-
-        unsafe { libc::isatty(fd) != 0 }
+        }
     }
 }
 
 #[cfg(windows)]
-use windows::isatty;
+fn isatty(stream: stream::Stream) -> bool {
+    windows::isatty(windows::stream_handle(stream))
+}
 #[cfg(windows)]
 mod windows {
-    extern crate kernel32;
-    extern crate winapi;
+    extern crate windows_sys;
+
+    use std::os::windows::io::RawHandle;
+
+    use self::windows_sys::Win32::Foundation::HANDLE;
+    use self::windows_sys::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, STD_ERROR_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE,
+    };
 
     use stream::Stream;
 
-    pub fn isatty(stream: Stream) -> bool {
-        let handle = match stream {
-            Stream::Stdout => winapi::winbase::STD_OUTPUT_HANDLE,
-            Stream::Stderr => winapi::winbase::STD_ERROR_HANDLE,
-        };
+    pub fn isatty(handle: RawHandle) -> bool {
+        let handle = handle as HANDLE;
 
         unsafe {
-            let handle = kernel32::GetStdHandle(handle);
-
-            // check for msys/cygwin
-            if is_cygwin_pty(handle) {
+            let mut out = 0;
+            if GetConsoleMode(handle, &mut out) != 0 {
                 return true;
             }
 
-            let mut out = 0;
-            kernel32::GetConsoleMode(handle, &mut out) != 0
+            // Not a native console. A msys/cygwin pty never reports itself
+            // as one via `GetConsoleMode`, so fall back to checking its
+            // file name for that.
+            is_cygwin_pty(handle)
         }
     }
 
-    /// Returns true if there is an MSYS/cygwin tty on the given handle.
-    fn is_cygwin_pty(handle: winapi::HANDLE) -> bool {
+    pub fn stream_handle(stream: Stream) -> RawHandle {
+        let which = match stream {
+            Stream::Stdin => STD_INPUT_HANDLE,
+            Stream::Stdout => STD_OUTPUT_HANDLE,
+            Stream::Stderr => STD_ERROR_HANDLE,
+        };
+
+        unsafe { GetStdHandle(which) as RawHandle }
+    }
+
+    /// Returns true if the given handle is a msys/cygwin pty.
+    ///
+    /// Only a successful name match counts as a pty: handles that aren't
+    /// files at all (e.g. sockets) fail the `GetFileInformationByHandleEx`
+    /// query and are treated as "not a pty" rather than assumed to be one,
+    /// since this is also reached from the `IsTerminal` impl for arbitrary
+    /// handles, not just the three standard console handles.
+    fn is_cygwin_pty(handle: HANDLE) -> bool {
         use std::ffi::OsString;
         use std::mem;
         use std::os::raw::c_void;
         use std::os::windows::ffi::OsStringExt;
         use std::slice;
 
-        use self::kernel32::GetFileInformationByHandleEx;
-        use self::winapi::fileapi::FILE_NAME_INFO;
-        use self::winapi::minwinbase::FileNameInfo;
-        use self::winapi::minwindef::MAX_PATH;
+        use self::windows_sys::Win32::Foundation::MAX_PATH;
+        use self::windows_sys::Win32::Storage::FileSystem::{
+            FileNameInfo, GetFileInformationByHandleEx, FILE_NAME_INFO,
+        };
 
         unsafe {
             let size = mem::size_of::<FILE_NAME_INFO>();
-            let mut name_info_bytes = vec![0u8; size + MAX_PATH];
+            let mut name_info_bytes = vec![0u8; size + MAX_PATH as usize];
             let res = GetFileInformationByHandleEx(handle,
                                                 FileNameInfo,
                                                 &mut *name_info_bytes as *mut _ as *mut c_void,
                                                 name_info_bytes.len() as u32);
             if res == 0 {
-                return true;
+                return false;
             }
             let name_info: FILE_NAME_INFO = *(name_info_bytes[0..size]
                 .as_ptr() as *const FILE_NAME_INFO);
@@ -175,3 +250,29 @@ mod redox {
         }
     }
 }
+
+#[cfg(target_os = "hermit")]
+use hermit::isatty;
+#[cfg(target_os = "hermit")]
+mod hermit {
+    extern crate hermit_abi;
+
+    use stream::Stream;
+
+    pub fn isatty(stream: Stream) -> bool {
+        let fd = match stream {
+            Stream::Stdin => hermit_abi::STDIN_FILENO,
+            Stream::Stdout => hermit_abi::STDOUT_FILENO,
+            Stream::Stderr => hermit_abi::STDERR_FILENO,
+        };
+
+        hermit_abi::isatty(fd)
+    }
+}
+
+// wasm32 has no concept of a tty, so every stream is reported as not one.
+// This lets CLI crates depend on us without a target-specific shim.
+#[cfg(target_arch = "wasm32")]
+fn isatty(_stream: stream::Stream) -> bool {
+    false
+}